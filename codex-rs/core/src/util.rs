@@ -1,4 +1,11 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+#[cfg(any(debug_assertions, test))]
+use std::sync::Arc;
+#[cfg(any(debug_assertions, test))]
+use std::sync::Mutex;
 use std::sync::OnceLock;
 use std::time::Duration;
 
@@ -9,12 +16,93 @@ use tracing::error;
 
 const INITIAL_DELAY_MS: u64 = 200;
 const BACKOFF_FACTOR: f64 = 2.0;
+const MAX_DELAY_MS: u64 = 30_000;
+const MAX_ATTEMPTS: u64 = 10;
+
+/// Jitter strategy used when computing a retry delay. See `backoff_with`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum JitterStrategy {
+    /// The original behaviour: exponential backoff scaled by a narrow
+    /// 0.9-1.1 multiplier.
+    Proportional,
+    /// AWS-style "full jitter": a uniform draw between zero and the
+    /// exponential delay, clamped to the policy's cap.
+    Full,
+    /// AWS-style "decorrelated jitter": a uniform draw between the base
+    /// delay and three times the previous sleep, which spreads out
+    /// retries from callers that all started backing off at once.
+    Decorrelated,
+}
+
+/// Tunable parameters for the retry loop, so callers can trade off
+/// aggressiveness (how fast we give up) against courtesy to a
+/// rate-limited endpoint (how long we wait between attempts).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct RetryPolicy {
+    pub base_delay: Duration,
+    pub cap: Duration,
+    pub max_attempts: u64,
+    pub jitter: JitterStrategy,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_millis(INITIAL_DELAY_MS),
+            cap: Duration::from_millis(MAX_DELAY_MS),
+            max_attempts: MAX_ATTEMPTS,
+            jitter: JitterStrategy::Proportional,
+        }
+    }
+}
 
 pub(crate) fn backoff(attempt: u64) -> Duration {
+    let policy = RetryPolicy::default();
+    let base_delay = policy.base_delay;
+    backoff_with(&policy, attempt, base_delay, None)
+}
+
+/// Compute how long to sleep before retry number `attempt` (1-indexed).
+///
+/// `prev_sleep` is the delay that was actually used for the previous
+/// attempt; it only matters for [`JitterStrategy::Decorrelated`], but is
+/// required unconditionally so callers thread it through as state rather
+/// than reconstructing it from `attempt`. `retry_after`, when present (e.g.
+/// parsed from an HTTP `Retry-After` header), takes precedence over any
+/// jitter strategy and is simply clamped to the policy's cap.
+pub(crate) fn backoff_with(
+    policy: &RetryPolicy,
+    attempt: u64,
+    prev_sleep: Duration,
+    retry_after: Option<Duration>,
+) -> Duration {
+    if let Some(retry_after) = retry_after {
+        return retry_after.min(policy.cap);
+    }
+
+    let base_ms = policy.base_delay.as_millis() as f64;
+    let cap_ms = policy.cap.as_millis() as f64;
     let exp = BACKOFF_FACTOR.powi(attempt.saturating_sub(1) as i32);
-    let base = (INITIAL_DELAY_MS as f64 * exp) as u64;
-    let jitter = rand::rng().random_range(0.9..1.1);
-    Duration::from_millis((base as f64 * jitter) as u64)
+
+    let delay_ms = match policy.jitter {
+        JitterStrategy::Proportional => {
+            let base = base_ms * exp;
+            let jitter = rand::rng().random_range(0.9..1.1);
+            (base * jitter).min(cap_ms)
+        }
+        JitterStrategy::Full => {
+            let capped = (base_ms * exp).min(cap_ms);
+            rand::rng().random_range(0.0..=capped)
+        }
+        JitterStrategy::Decorrelated => {
+            let prev_ms = prev_sleep.as_millis() as f64;
+            let lo = base_ms.min(cap_ms);
+            let upper = (prev_ms * 3.0).max(lo).min(cap_ms);
+            rand::rng().random_range(lo..=upper)
+        }
+    };
+
+    Duration::from_millis(delay_ms as u64)
 }
 
 pub(crate) fn error_or_panic(message: String) {
@@ -40,12 +128,89 @@ pub(crate) fn try_parse_error_message(text: &str) -> String {
     text.to_string()
 }
 
-/// Strip model-emitted citation markup so it does not leak into user-visible text.
-///
-/// Handles both private-use-wrapped blocks (e.g., `citeturn2`) and
-/// angle-bracket forms (`<cite|path:line|>`). Returns a borrowed `Cow` when
-/// nothing changes to avoid allocations on the hot path.
-pub fn strip_citation_markup(text: &str) -> Cow<'_, str> {
+/// Error codes the server is known to emit for conditions that will not
+/// resolve on their own, no matter how many times we retry.
+const TERMINAL_ERROR_CODES: &[&str] = &["refresh_token_reused"];
+
+/// Error codes the server is known to emit for conditions that are
+/// expected to clear up if we wait and try again.
+const TRANSIENT_ERROR_CODES: &[&str] = &["rate_limit_exceeded", "server_error"];
+
+/// A server error response, parsed into its structured fields so the
+/// retry loop can decide whether to give up or try again, and so the UI
+/// can surface a clean message instead of raw JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ServerError {
+    pub status: u16,
+    pub message: String,
+    pub error_type: Option<String>,
+    pub code: Option<String>,
+    pub param: Option<String>,
+}
+
+impl ServerError {
+    /// Whether this error is worth retrying: a rate limit or server-side
+    /// failure, as opposed to a terminal client error like a bad request
+    /// or an already-used refresh token.
+    ///
+    /// Note that `invalid_request_error` is a `type`, not a `code` — the
+    /// server's own fixtures pair it with codes like `refresh_token_reused`
+    /// or no code at all, so it must be checked against `error_type`.
+    pub(crate) fn is_retryable(&self) -> bool {
+        if self.error_type.as_deref() == Some("invalid_request_error") {
+            return false;
+        }
+        if let Some(code) = self.code.as_deref() {
+            if TERMINAL_ERROR_CODES.contains(&code) {
+                return false;
+            }
+            if TRANSIENT_ERROR_CODES.contains(&code) {
+                return true;
+            }
+        }
+        matches!(self.status, 429 | 500..=599)
+    }
+}
+
+/// Parse a server error response into a [`ServerError`], falling back to
+/// the raw response text as the message when it is not valid JSON (or
+/// does not contain an `error` object).
+pub(crate) fn parse_server_error(status: u16, body: &str) -> ServerError {
+    debug!("Parsing server error response ({status}): {body}");
+    let json = serde_json::from_str::<serde_json::Value>(body).unwrap_or_default();
+    let error = json.get("error");
+
+    let message = error
+        .and_then(|error| error.get("message"))
+        .and_then(|message| message.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            if body.is_empty() {
+                "Unknown error".to_string()
+            } else {
+                body.to_string()
+            }
+        });
+
+    ServerError {
+        status,
+        message,
+        error_type: error
+            .and_then(|error| error.get("type"))
+            .and_then(|value| value.as_str())
+            .map(str::to_string),
+        code: error
+            .and_then(|error| error.get("code"))
+            .and_then(|value| value.as_str())
+            .map(str::to_string),
+        param: error
+            .and_then(|error| error.get("param"))
+            .and_then(|value| value.as_str())
+            .map(str::to_string),
+    }
+}
+
+fn citation_regexes() -> (&'static Regex, &'static Regex) {
     static PUA_RE: OnceLock<Regex> = OnceLock::new();
     static ANGLE_RE: OnceLock<Regex> = OnceLock::new();
 
@@ -57,18 +222,336 @@ pub fn strip_citation_markup(text: &str) -> Cow<'_, str> {
         Regex::new(r"<cite\|([\s\S]*?)\|>")
             .unwrap_or_else(|_| panic!("invalid angle citation regex"))
     });
+    (re_pua, re_angle)
+}
+
+/// A citation recovered from model-emitted markup.
+///
+/// `byte_range` is the offset of the citation *in the cleaned output*
+/// returned alongside it, not in the original text, so a renderer can use
+/// it to re-anchor a link without re-scanning for the markup it replaced.
+/// `resolved_url` is left `None` until a caller opts into resolving
+/// web-search citations via [`resolve_web_citations`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation {
+    pub raw: String,
+    pub path: Option<PathBuf>,
+    pub line: Option<u32>,
+    pub byte_range: Range<usize>,
+    pub resolved_url: Option<String>,
+}
+
+/// Split `path:line` on the last `:` so a path containing colons doesn't
+/// get mistaken for a line-number separator unless the suffix after the
+/// final `:` actually parses as a number.
+fn split_path_and_line(inner: &str) -> (Option<PathBuf>, Option<u32>) {
+    if let Some((path, line)) = inner.rsplit_once(':')
+        && let Ok(line) = line.parse::<u32>()
+    {
+        return (Some(PathBuf::from(path)), Some(line));
+    }
+    (Some(PathBuf::from(inner)), None)
+}
+
+/// Strip model-emitted citation markup, returning the cleaned text plus
+/// the citations that were found in it.
+///
+/// Handles both private-use-wrapped blocks (e.g., `citeturn2`), which are
+/// classified as web-search citations and removed outright, and
+/// angle-bracket forms (`<cite|path:line|>`), which are unwrapped to their
+/// `path:line` text and parsed by splitting on the last `:`. Returns a
+/// borrowed `Cow` when nothing changes to avoid allocations on the hot
+/// path.
+pub fn extract_citations(text: &str) -> (Cow<'_, str>, Vec<Citation>) {
+    let (re_pua, re_angle) = citation_regexes();
+
+    if re_pua.find(text).is_none() && !re_angle.is_match(text) {
+        return (Cow::Borrowed(text), Vec::new());
+    }
+
+    enum Kind<'a> {
+        Web,
+        Path(&'a str),
+    }
+
+    let mut matches: Vec<(Range<usize>, Kind)> = re_pua
+        .find_iter(text)
+        .map(|m| (m.range(), Kind::Web))
+        .chain(re_angle.captures_iter(text).map(|captures| {
+            let whole = captures.get(0).expect("regex always has a full match");
+            let inner = captures.get(1).expect("angle citation has a capture group");
+            (whole.range(), Kind::Path(inner.as_str()))
+        }))
+        .collect();
+    matches.sort_by_key(|(range, _)| range.start);
+
+    // Drop matches that overlap one we're already keeping *before* walking
+    // the text, so the gap-copying below never straddles a dropped match's
+    // raw markup and leaks it into the cleaned output.
+    let mut dedup_end = 0;
+    matches.retain(|(range, _)| {
+        if range.start < dedup_end {
+            return false;
+        }
+        dedup_end = range.end;
+        true
+    });
+
+    let mut cleaned = String::with_capacity(text.len());
+    let mut citations = Vec::with_capacity(matches.len());
+    let mut last_end = 0;
+
+    for (range, kind) in matches {
+        cleaned.push_str(&text[last_end..range.start]);
+        let raw = text[range.clone()].to_string();
+        let start = cleaned.len();
+        let (path, line) = match kind {
+            Kind::Web => (None, None),
+            Kind::Path(inner) => {
+                cleaned.push_str(inner);
+                split_path_and_line(inner)
+            }
+        };
+        citations.push(Citation {
+            raw,
+            path,
+            line,
+            byte_range: start..cleaned.len(),
+            resolved_url: None,
+        });
+        last_end = range.end;
+    }
+    cleaned.push_str(&text[last_end..]);
+
+    (Cow::Owned(cleaned), citations)
+}
+
+/// Strip model-emitted citation markup so it does not leak into user-visible text.
+///
+/// Thin wrapper around [`extract_citations`] for callers that only care
+/// about the cleaned text.
+pub fn strip_citation_markup(text: &str) -> Cow<'_, str> {
+    extract_citations(text).0
+}
+
+/// Resolve a possibly-shortened or tracking URL to its final destination by
+/// issuing an HTTP `HEAD` request and reading the response URL after
+/// redirects have been followed. Returns `None` on any failure so callers
+/// can fall back to the original URL.
+///
+/// Only transient failures are retried, using this module's
+/// [`RetryPolicy`] and [`backoff_with`]: connection/timeout errors, and
+/// HTTP error responses that [`ServerError::is_retryable`] says are worth
+/// another attempt. A malformed URL or other non-retryable outcome
+/// returns immediately without sleeping. Every attempt is recorded into
+/// `tracker` (keyed by `url`) so tests can assert on the retry behaviour
+/// without timing hacks.
+pub(crate) async fn resolve_citation_url(
+    client: &reqwest::Client,
+    url: &str,
+    tracker: &RetryTracker,
+) -> Option<String> {
+    let policy = RetryPolicy::default();
+    let mut prev_sleep = policy.base_delay;
 
-    if re_pua.find(text).is_some() {
-        let replaced = re_pua.replace_all(text, "");
-        if re_angle.is_match(replaced.as_ref()) {
-            Cow::Owned(re_angle.replace_all(replaced.as_ref(), "$1").into_owned())
-        } else {
-            Cow::Owned(replaced.into_owned())
+    for attempt in 1..=policy.max_attempts {
+        tracker.record(url);
+        let should_retry = match client.head(url).send().await {
+            Ok(response) if response.status().is_success() => {
+                return Some(response.url().to_string());
+            }
+            Ok(response) => {
+                let status = response.status().as_u16();
+                let body = response.text().await.unwrap_or_default();
+                let error = parse_server_error(status, &body);
+                debug!(
+                    "HEAD {url} got {status} (attempt {attempt}): {}",
+                    error.message
+                );
+                error.is_retryable()
+            }
+            Err(err) if err.is_connect() || err.is_timeout() => {
+                debug!("HEAD {url} failed (attempt {attempt}), retrying: {err}");
+                true
+            }
+            Err(err) => {
+                debug!("HEAD {url} failed, giving up: {err}");
+                false
+            }
+        };
+
+        if !should_retry || attempt == policy.max_attempts {
+            return None;
+        }
+
+        let sleep = backoff_with(&policy, attempt, prev_sleep, None);
+        prev_sleep = sleep;
+        tokio::time::sleep(sleep).await;
+    }
+    None
+}
+
+/// Extract the destination URL a web-search citation embeds, if any (e.g.
+/// `...search0 https://example.com/page`). Most citations carry no URL at
+/// all — just a `turn`/`search` index referring to a separate results list
+/// — so this returns `None` far more often than not, and callers must
+/// treat that as "nothing to resolve" rather than feeding the opaque
+/// markup itself to an HTTP client.
+fn web_citation_url(raw: &str) -> Option<String> {
+    let start = raw.find("http://").or_else(|| raw.find("https://"))?;
+    let rest = &raw[start..];
+    let end = rest
+        .find(|c: char| c.is_whitespace() || c == '\u{e201}')
+        .unwrap_or(rest.len());
+    Some(rest[..end].to_string())
+}
+
+/// Resolve the `resolved_url` of every web-search citation in `citations`
+/// that embeds a destination URL (angle-form citations, which already
+/// carry a `path`, are left alone, as are web-search citations with
+/// nothing to resolve). Resolutions are cached by the embedded URL for
+/// the duration of this call so duplicate citations in the same response
+/// are only resolved once.
+pub(crate) async fn resolve_web_citations(
+    client: &reqwest::Client,
+    citations: &mut [Citation],
+    tracker: &RetryTracker,
+) {
+    let mut cache: HashMap<String, Option<String>> = HashMap::new();
+
+    for citation in citations.iter_mut() {
+        if citation.path.is_some() {
+            continue;
+        }
+        let Some(url) = web_citation_url(&citation.raw) else {
+            continue;
+        };
+        let resolved = match cache.get(&url) {
+            Some(resolved) => resolved.clone(),
+            None => {
+                let resolved = resolve_citation_url(client, &url, tracker).await;
+                cache.insert(url, resolved.clone());
+                resolved
+            }
+        };
+        citation.resolved_url = resolved;
+    }
+}
+
+/// Like [`extract_citations`], but also resolves `resolved_url` for any
+/// web-search citations that embed a destination URL. This is the
+/// opt-in entry point for callers with an HTTP client available;
+/// [`extract_citations`] itself stays synchronous and never touches the
+/// network.
+pub async fn extract_citations_with_resolved_urls<'a>(
+    client: &reqwest::Client,
+    text: &'a str,
+    tracker: &RetryTracker,
+) -> (Cow<'a, str>, Vec<Citation>) {
+    let (cleaned, mut citations) = extract_citations(text);
+    resolve_web_citations(client, &mut citations, tracker).await;
+    (cleaned, citations)
+}
+
+/// How an expected attempt count should be compared against the actual
+/// count recorded by a [`RetryTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ComparisonKind {
+    /// The actual count must equal the expectation exactly.
+    Exact,
+    /// The actual count must be greater than or equal to the expectation.
+    AtLeast,
+}
+
+#[cfg(any(debug_assertions, test))]
+#[derive(Debug, Default)]
+struct RetryTrackerInner {
+    attempts: HashMap<String, u64>,
+    expectations: Vec<(String, u64, ComparisonKind)>,
+}
+
+/// Records per-operation retry-attempt counts, keyed by a caller-supplied
+/// label, so tests can assert things like "this request retried at least
+/// twice before succeeding" without timing hacks. Compiles to a
+/// zero-overhead no-op in release builds so there is no cost on the hot
+/// retry path in production.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RetryTracker {
+    #[cfg(any(debug_assertions, test))]
+    inner: Arc<Mutex<RetryTrackerInner>>,
+}
+
+impl RetryTracker {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an attempt for `label`. Called once per attempt from the
+    /// retry loop.
+    #[cfg(any(debug_assertions, test))]
+    pub(crate) fn record(&self, label: &str) {
+        let mut inner = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *inner.attempts.entry(label.to_string()).or_insert(0) += 1;
+    }
+
+    #[cfg(not(any(debug_assertions, test)))]
+    pub(crate) fn record(&self, _label: &str) {}
+
+    /// Record an expectation to be checked by [`RetryTracker::verify`].
+    #[cfg(any(debug_assertions, test))]
+    pub(crate) fn expect_attempts(&self, label: &str, count: u64, kind: ComparisonKind) {
+        let mut inner = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        inner.expectations.push((label.to_string(), count, kind));
+    }
+
+    #[cfg(not(any(debug_assertions, test)))]
+    pub(crate) fn expect_attempts(&self, _label: &str, _count: u64, _kind: ComparisonKind) {}
+
+    /// Check every recorded expectation against the actual attempt counts,
+    /// panicking (via [`error_or_panic`]) on the first violation.
+    #[cfg(any(debug_assertions, test))]
+    pub(crate) fn verify(&self) {
+        let inner = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (label, expected, kind) in &inner.expectations {
+            let actual = inner.attempts.get(label).copied().unwrap_or(0);
+            let satisfied = match kind {
+                ComparisonKind::Exact => actual == *expected,
+                ComparisonKind::AtLeast => actual >= *expected,
+            };
+            if !satisfied {
+                error_or_panic(format!(
+                    "retry tracker expectation failed for {label:?}: expected {kind:?} {expected}, got {actual}"
+                ));
+            }
+        }
+    }
+
+    #[cfg(not(any(debug_assertions, test)))]
+    pub(crate) fn verify(&self) {}
+}
+
+#[cfg(any(debug_assertions, test))]
+impl Drop for RetryTracker {
+    /// Acts as a guard so tests that forget to call `verify()` explicitly
+    /// still get their expectations checked. Only fires when the last
+    /// clone of a shared tracker is dropped, so passing clones into
+    /// in-flight retry attempts doesn't verify against counts that
+    /// haven't finished accumulating yet. Also skipped while already
+    /// unwinding from a panic so a failed expectation doesn't trigger a
+    /// double-panic abort.
+    fn drop(&mut self) {
+        if Arc::strong_count(&self.inner) == 1 && !std::thread::panicking() {
+            self.verify();
         }
-    } else if re_angle.is_match(text) {
-        Cow::Owned(re_angle.replace_all(text, "$1").into_owned())
-    } else {
-        Cow::Borrowed(text)
     }
 }
 
@@ -76,6 +559,20 @@ pub fn strip_citation_markup(text: &str) -> Cow<'_, str> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn backoff_with_decorrelated_handles_cap_below_base_delay() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(500),
+            cap: Duration::from_millis(100),
+            max_attempts: 5,
+            jitter: JitterStrategy::Decorrelated,
+        };
+        // Must not panic even though `cap < base_delay` collapses the
+        // random range to a single point.
+        let delay = backoff_with(&policy, 1, policy.base_delay, None);
+        assert_eq!(delay, Duration::from_millis(100));
+    }
+
     #[test]
     fn test_try_parse_error_message() {
         let text = r#"{
@@ -100,6 +597,50 @@ mod tests {
         assert_eq!(message, r#"{"message": "test"}"#);
     }
 
+    #[test]
+    fn parse_server_error_extracts_all_fields() {
+        let text = r#"{
+  "error": {
+    "message": "Your refresh token has already been used to generate a new access token. Please try signing in again.",
+    "type": "invalid_request_error",
+    "param": null,
+    "code": "refresh_token_reused"
+  }
+}"#;
+        let error = parse_server_error(400, text);
+        assert_eq!(
+            error.message,
+            "Your refresh token has already been used to generate a new access token. Please try signing in again."
+        );
+        assert_eq!(error.error_type.as_deref(), Some("invalid_request_error"));
+        assert_eq!(error.code.as_deref(), Some("refresh_token_reused"));
+        assert_eq!(error.param, None);
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn parse_server_error_falls_back_to_raw_text() {
+        let error = parse_server_error(503, "upstream timed out");
+        assert_eq!(error.message, "upstream timed out");
+        assert_eq!(error.error_type, None);
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn is_retryable_covers_rate_limits_and_server_errors() {
+        let rate_limited = parse_server_error(
+            429,
+            r#"{"error": {"message": "slow down", "code": "rate_limit_exceeded"}}"#,
+        );
+        assert!(rate_limited.is_retryable());
+
+        let bad_request = parse_server_error(
+            400,
+            r#"{"error": {"message": "bad request", "type": "invalid_request_error"}}"#,
+        );
+        assert!(!bad_request.is_retryable());
+    }
+
     #[test]
     fn strip_citation_markup_removes_private_use_block() {
         let src = "Hello citeturn2search0 world";
@@ -113,4 +654,71 @@ mod tests {
         let out = strip_citation_markup(src);
         assert_eq!(out, "See web/src/foo.svelte:1 for details");
     }
+
+    #[test]
+    fn extract_citations_returns_path_and_line() {
+        let src = "See <cite|web/src/foo.svelte:1|> for details";
+        let (cleaned, citations) = extract_citations(src);
+        assert_eq!(cleaned, "See web/src/foo.svelte:1 for details");
+        assert_eq!(citations.len(), 1);
+        let citation = &citations[0];
+        assert_eq!(citation.raw, "<cite|web/src/foo.svelte:1|>");
+        assert_eq!(citation.path, Some(PathBuf::from("web/src/foo.svelte")));
+        assert_eq!(citation.line, Some(1));
+        assert_eq!(
+            &cleaned[citation.byte_range.clone()],
+            "web/src/foo.svelte:1"
+        );
+    }
+
+    #[test]
+    fn extract_citations_classifies_web_search_blocks() {
+        let src = "Hello \u{e200}cite\u{e202}turn2search0\u{e201} world";
+        let (cleaned, citations) = extract_citations(src);
+        assert_eq!(cleaned, "Hello  world");
+        assert_eq!(citations.len(), 1);
+        assert_eq!(citations[0].path, None);
+        assert_eq!(citations[0].line, None);
+        assert_eq!(citations[0].byte_range, "Hello ".len().."Hello ".len());
+    }
+
+    #[test]
+    fn web_citation_url_extracts_embedded_link() {
+        let raw = "\u{e200}citeturn2search0 https://example.com/redirect\u{e201}";
+        assert_eq!(
+            web_citation_url(raw),
+            Some("https://example.com/redirect".to_string())
+        );
+    }
+
+    #[test]
+    fn web_citation_url_absent_when_no_link_embedded() {
+        assert_eq!(web_citation_url("\u{e200}citeturn2search0\u{e201}"), None);
+    }
+
+    #[test]
+    fn extract_citations_no_markup_borrows_input() {
+        let src = "nothing to see here";
+        let (cleaned, citations) = extract_citations(src);
+        assert!(matches!(cleaned, Cow::Borrowed(_)));
+        assert!(citations.is_empty());
+    }
+
+    #[test]
+    fn retry_tracker_exact_expectation_passes_when_met() {
+        let tracker = RetryTracker::new();
+        tracker.record("list_models");
+        tracker.record("list_models");
+        tracker.expect_attempts("list_models", 2, ComparisonKind::Exact);
+        tracker.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "retry tracker expectation failed")]
+    fn retry_tracker_at_least_expectation_panics_when_unmet() {
+        let tracker = RetryTracker::new();
+        tracker.record("list_models");
+        tracker.expect_attempts("list_models", 2, ComparisonKind::AtLeast);
+        tracker.verify();
+    }
 }